@@ -0,0 +1,99 @@
+use bytes::{Buf, Bytes, BytesMut};
+use redis_protocol::error::RedisProtocolError;
+use redis_protocol::resp3::decode::streaming::decode_mut;
+use redis_protocol::resp3::types::BytesFrame;
+use tokio_util::codec::{Decoder, Encoder};
+
+// Reply shapes the server can write back to a client. Kept as a small enum
+// instead of round-tripping through a full `BytesFrame` because today we
+// only ever emit these four (mirrors the old write_simple_ok/write_nil_bulk/
+// write_bulk/write_err helpers this codec replaces).
+#[derive(Debug)]
+pub enum Reply {
+    Ok,
+    NilBulk,
+    Bulk(Bytes),
+    Error(&'static str),
+}
+
+#[derive(Debug)]
+pub enum CodecError {
+    Io(std::io::Error),
+    Protocol(RedisProtocolError),
+}
+
+impl From<std::io::Error> for CodecError {
+    fn from(e: std::io::Error) -> Self {
+        CodecError::Io(e)
+    }
+}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecError::Io(e) => write!(f, "{e}"),
+            CodecError::Protocol(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+// `Decoder`/`Encoder` pair for RESP3, meant to be driven through
+// `tokio_util::codec::{FramedRead, FramedWrite}`. Replaces the hand-rolled
+// `Resp3Handler` + fixed-size `read_buf` loop: `decode` borrows straight from
+// the accumulating `BytesMut` instead of copying into a scratch buffer per
+// call, and partial frames fall out naturally as `Ok(None)`.
+#[derive(Default)]
+pub struct Resp3Codec;
+
+impl Decoder for Resp3Codec {
+    type Item = BytesFrame;
+    type Error = CodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match decode_mut(buf) {
+            // Partial frame: leave `buf` untouched and wait for more bytes.
+            Ok(None) => Ok(None),
+            Ok(Some((decoded, amt, _))) => {
+                buf.advance(amt);
+                match decoded.into_complete_frame() {
+                    Ok(frame) => Ok(Some(frame)),
+                    // Streamed (chunked) RESP3 frames aren't supported by the
+                    // GET/SET fast path. `Resp3Codec` has no state to resume
+                    // a streamed decode across calls, so there's no way to
+                    // wait for "more data" here without desyncing the
+                    // connection forever; surface it as a protocol error
+                    // instead, same as a malformed frame.
+                    Err(e) => Err(CodecError::Protocol(e)),
+                }
+            }
+            Err(e) => Err(CodecError::Protocol(e)),
+        }
+    }
+}
+
+impl Encoder<Reply> for Resp3Codec {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, reply: Reply, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match reply {
+            Reply::Ok => dst.extend_from_slice(b"+OK\r\n"),
+            Reply::NilBulk => dst.extend_from_slice(b"$-1\r\n"),
+            Reply::Bulk(data) => {
+                let mut len_buf = itoa::Buffer::new();
+                dst.extend_from_slice(b"$");
+                dst.extend_from_slice(len_buf.format(data.len()).as_bytes());
+                dst.extend_from_slice(b"\r\n");
+                dst.extend_from_slice(&data);
+                dst.extend_from_slice(b"\r\n");
+            }
+            Reply::Error(msg) => {
+                dst.extend_from_slice(b"-ERR ");
+                dst.extend_from_slice(msg.as_bytes());
+                dst.extend_from_slice(b"\r\n");
+            }
+        }
+        Ok(())
+    }
+}
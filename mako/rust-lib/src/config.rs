@@ -0,0 +1,48 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+// Where a listener should bind. TCP for remote clients, Unix domain socket
+// for local clients/benchmarks on the same host that want to skip the TCP
+// stack entirely, WebSocket for browser/relayed clients limited to HTTP/WS
+// egress.
+#[derive(Clone, Debug)]
+pub enum BindAddr {
+    Tcp(String),
+    Unix(String),
+    Ws(String),
+}
+
+// C-side description of one listener, passed into `rust_init` as an array.
+// `kind` mirrors `BindAddr`'s variants (0 = tcp, 1 = unix, 2 = ws) rather
+// than exposing the Rust enum directly across the FFI boundary.
+#[repr(C)]
+pub struct CListenerConfig {
+    pub kind: u32,
+    pub address: *const c_char,
+}
+
+// Copies every `CListenerConfig` into an owned `BindAddr` so nothing past
+// this call depends on C-owned memory. Entries with an unrecognized `kind`
+// or a non-UTF8 address are dropped rather than failing the whole batch.
+//
+// # Safety
+// `ptr` must point to `len` valid, initialized `CListenerConfig`s whose
+// `address` fields are NUL-terminated C strings that outlive this call.
+pub unsafe fn parse_listener_configs(ptr: *const CListenerConfig, len: usize) -> Vec<BindAddr> {
+    if ptr.is_null() || len == 0 {
+        return Vec::new();
+    }
+
+    std::slice::from_raw_parts(ptr, len)
+        .iter()
+        .filter_map(|c| {
+            let address = CStr::from_ptr(c.address).to_str().ok()?.to_owned();
+            match c.kind {
+                0 => Some(BindAddr::Tcp(address)),
+                1 => Some(BindAddr::Unix(address)),
+                2 => Some(BindAddr::Ws(address)),
+                _ => None,
+            }
+        })
+        .collect()
+}
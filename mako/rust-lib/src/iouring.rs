@@ -0,0 +1,99 @@
+// Alternative server event loop built on `compio`, so Linux deployments can
+// run the GET/SET pipeline on io_uring (completion-based I/O) instead of the
+// epoll-based Tokio reactor. RESP3 parsing and the FFI bridge are shared
+// with the Tokio path (`crate::codec`, `crate::ffi_getset_batch`) — only the
+// I/O submission layer differs: compio's read/write APIs take an owned
+// buffer and hand it back filled on completion, rather than borrowing a
+// `&mut [u8]`.
+//
+// Gated behind the `iouring` build feature; the Tokio path in lib.rs stays
+// the default. TODO: there's no Cargo.toml in this checkout yet to declare
+// the feature in, so `cargo build --features iouring` won't select this
+// module until one exists.
+#![cfg(feature = "iouring")]
+
+use bytes::BytesMut;
+use compio::io::{AsyncReadExt, AsyncWriteExt};
+use compio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::Resp3Codec;
+use crate::{classify_frame, process_batch, PipelineItem};
+
+const READ_CHUNK: usize = 16 * 1024;
+
+// Spawns the io_uring accept loop on its own OS thread with its own compio
+// runtime, mirroring how `rust_init` spawns the Tokio runtime's thread.
+pub fn spawn(bind_addr: String) {
+    std::thread::spawn(move || {
+        compio::runtime::Runtime::new()
+            .expect("failed to build compio runtime")
+            .block_on(async move {
+                if let Err(e) = serve(&bind_addr).await {
+                    eprintln!("io_uring server error: {e}");
+                }
+            });
+    });
+}
+
+async fn serve(bind_addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(bind_addr).await?;
+    println!("io_uring Rust server listening on {bind_addr}");
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        compio::runtime::spawn(async move {
+            if let Err(e) = handle_client(stream).await {
+                eprintln!("io_uring client handling error: {e}");
+            }
+        })
+        .detach();
+    }
+}
+
+async fn handle_client(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut codec = Resp3Codec;
+    let mut read_buf = BytesMut::new();
+    let mut write_buf = BytesMut::new();
+
+    loop {
+        // Hand compio an owned buffer; it reads into it and gives it back
+        // filled up to the completion length, rather than us passing a
+        // `&mut [u8]` for epoll to fill in place.
+        let owned = vec![0u8; READ_CHUNK];
+        let (res, owned) = stream.read(owned).await;
+        let n = res?;
+        if n == 0 {
+            break;
+        }
+        read_buf.extend_from_slice(&owned[..n]);
+
+        let mut frames = Vec::new();
+        loop {
+            match codec.decode(&mut read_buf) {
+                Ok(Some(frame)) => frames.push(Ok(frame)),
+                Ok(None) => break,
+                Err(e) => {
+                    frames.push(Err(e));
+                    break;
+                }
+            }
+        }
+
+        let items: Vec<PipelineItem> = frames.into_iter().map(classify_frame).collect();
+        let outcome = process_batch(items);
+
+        for reply in outcome.replies {
+            codec.encode(reply, &mut write_buf)?;
+        }
+
+        let (res, _) = stream.write_all(write_buf.split().to_vec()).await;
+        res?;
+
+        if outcome.close_after {
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -1,13 +1,48 @@
-use tokio::net::{TcpListener, TcpStream};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::io::BufWriter;
-use tokio::io::AsyncWrite;
-use bytes::{Bytes, BytesMut};
-use redis_protocol::resp3::{types::BytesFrame, types::DecodedFrame};
-use redis_protocol::error::RedisProtocolError;
+use std::sync::OnceLock;
+
+use tokio::io::{self, AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use bytes::Bytes;
+use futures::{FutureExt, SinkExt, StreamExt};
+use redis_protocol::resp3::types::BytesFrame;
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+mod codec;
+use codec::{Reply, Resp3Codec};
+
+mod config;
+use config::{BindAddr, CListenerConfig};
+
+mod listener;
+use listener::StreamListener;
+
+// io_uring backend (see iouring.rs for why it only kicks in behind the
+// `iouring` feature).
+#[cfg(feature = "iouring")]
+mod iouring;
+
+mod tls;
+use tls::RESP3_ALPN_PROTOCOL;
+
+mod ws;
+
+// Lets `rust_shutdown` reach into the runtime `rust_init` spawned, without
+// the C++ host having to hold on to a handle itself.
+struct ServerHandle {
+    token: CancellationToken,
+    tracker: TaskTracker,
+    runtime: tokio::runtime::Handle,
+}
+
+static SERVER_HANDLE: OnceLock<ServerHandle> = OnceLock::new();
 
-mod resp3_handler;
-use resp3_handler::Resp3Handler;
+// Paths to the PEM cert chain + private key used to terminate TLS for
+// `rediss://` clients. TODO: thread these through `rust_init`'s config
+// struct instead of hard-coding once that lands.
+const TLS_CERT_PATH: &str = "certs/mako.crt";
+const TLS_KEY_PATH: &str = "certs/mako.key";
 
 extern "C" {
     // GET/SET single-call interface returning an optional malloc'd buffer for GET
@@ -20,6 +55,36 @@ extern "C" {
 
     // free buffer returned by cpp_execute_request (if any)
     fn cpp_free_buf(ptr: *mut u8, len: usize);
+
+    // Batched GET/SET: crosses into C++ once per pipeline batch instead of
+    // once per command. Writes one `BatchResult` per op, in order, into
+    // `out_results_ptr` (caller-owned, `n_ops` elements).
+    fn cpp_execute_batch(ops_ptr: *const BatchOp, n_ops: usize, out_results_ptr: *mut BatchResult) -> bool;
+
+    // Frees every GET-hit buffer referenced by a result block in one call,
+    // instead of one `cpp_free_buf` per key.
+    fn cpp_free_batch(results_ptr: *mut BatchResult, n_results: usize);
+}
+
+// One op in a batched FFI request. Plain `#[repr(C)]` struct rather than a
+// `Command` reference so the layout is FFI-stable independent of `Bytes`'s
+// internals.
+#[repr(C)]
+struct BatchOp {
+    op: u32,
+    key_ptr: *const u8,
+    key_len: usize,
+    val_ptr: *const u8,
+    val_len: usize,
+}
+
+// One result slot, populated by `cpp_execute_batch` in the same order as
+// the `BatchOp`s it was given.
+#[repr(C)]
+struct BatchResult {
+    ok: bool,
+    out_ptr: *mut u8,
+    out_len: usize,
 }
 
 #[derive(Copy, Clone)]
@@ -57,10 +122,9 @@ fn parse_opcode(name: &[u8]) -> OpCode {
 
 // Parse only GET/SET from a RESP3 frame, with zero string allocations.
 // Everything else will be treated as "unsupported" for now.
-fn parse_resp3(frame: DecodedFrame<BytesFrame>) -> Option<Command> {
+fn parse_resp3(frame: BytesFrame) -> Option<Command> {
     use BytesFrame::*;
-    let f = frame.into_complete_frame().ok()?;
-    let parts = match f {
+    let parts = match frame {
         Array { data, .. } => data,
         _ => return None,
     };
@@ -97,34 +161,8 @@ fn parse_resp3(frame: DecodedFrame<BytesFrame>) -> Option<Command> {
     }
 }
 
-// ===== RESP writers (no big String formatting) =====
-
-#[inline]
-async fn write_simple_ok<W: AsyncWrite + Unpin>(stream: &mut W) -> std::io::Result<()> {
-    stream.write_all(b"+OK\r\n").await
-}
-
-#[inline]
-async fn write_nil_bulk<W: AsyncWrite + Unpin>(stream: &mut W) -> std::io::Result<()> {
-    stream.write_all(b"$-1\r\n").await
-}
-
-#[inline]
-async fn write_bulk<W: AsyncWrite + Unpin>(stream: &mut W, data: &[u8]) -> std::io::Result<()> {
-    let mut buf = itoa::Buffer::new();
-    stream.write_all(b"$").await?;
-    stream.write_all(buf.format(data.len()).as_bytes()).await?;
-    stream.write_all(b"\r\n").await?;
-    stream.write_all(data).await?;
-    stream.write_all(b"\r\n").await
-}
-
-#[inline]
-async fn write_err<W: AsyncWrite + Unpin>(stream: &mut W, msg: &str) -> std::io::Result<()> {
-    stream.write_all(b"-ERR ").await?;
-    stream.write_all(msg.as_bytes()).await?;
-    stream.write_all(b"\r\n").await
-}
+// RESP replies are now written through `codec::Resp3Codec`'s `Encoder<Reply>`
+// impl (see codec.rs) rather than hand-written per-reply helpers.
 
 // ===== FFI bridge for GET/SET =====
 
@@ -163,10 +201,197 @@ fn ffi_getset(cmd: &Command) -> Result<Option<&'static [u8]>, ()> {
     }
 }
 
+// Owns the raw `BatchResult` block returned by `cpp_execute_batch` for just
+// long enough that the caller can write out every GET hit it points to,
+// then free all of those GET buffers with one `cpp_free_batch` call (the
+// slot array itself is freed by `Drop` below).
+struct BatchResults {
+    ptr: *mut BatchResult,
+    len: usize,
+}
+
+// `ffi_getset_batch` leaks the `Vec<BatchResult>` backing allocation into
+// `ptr`/`len` so it survives past the call (the caller needs it alive while
+// reading GET-hit buffers out of it). This reclaims and drops that Rust-side
+// allocation once the caller is done with it. Note this is independent of
+// `cpp_free_batch`, which frees the C-side GET buffers the slots *point to*,
+// not the slot array itself.
+impl Drop for BatchResults {
+    fn drop(&mut self) {
+        unsafe { drop(Vec::from_raw_parts(self.ptr, self.len, self.len)) };
+    }
+}
+
+// Batched counterpart to `ffi_getset`: crosses into C++ once for the whole
+// pipeline batch instead of once per command. Returns one result per `cmds`
+// entry, in order, plus the raw result block whose GET-hit buffers the
+// caller must free via `cpp_free_batch` once it's done reading them.
+fn ffi_getset_batch(cmds: &[Command]) -> (Vec<Result<Option<&'static [u8]>, ()>>, BatchResults) {
+    let ops: Vec<BatchOp> = cmds
+        .iter()
+        .map(|cmd| {
+            let (val_ptr, val_len) = match &cmd.val {
+                Some(v) => (v.as_ptr(), v.len()),
+                None => (std::ptr::null(), 0),
+            };
+            BatchOp {
+                op: cmd.op as u32,
+                key_ptr: cmd.key.as_ptr(),
+                key_len: cmd.key.len(),
+                val_ptr,
+                val_len,
+            }
+        })
+        .collect();
+
+    let mut results: Vec<BatchResult> = (0..ops.len())
+        .map(|_| BatchResult { ok: false, out_ptr: std::ptr::null_mut(), out_len: 0 })
+        .collect();
+
+    let ok = unsafe { cpp_execute_batch(ops.as_ptr(), ops.len(), results.as_mut_ptr()) };
+
+    let raw = BatchResults { ptr: results.as_mut_ptr(), len: results.len() };
+    std::mem::forget(results); // ownership of the buffer now lives in `raw` until it is dropped
+
+    if !ok {
+        return (cmds.iter().map(|_| Err(())).collect(), raw);
+    }
+
+    // SAFETY: `raw.ptr` is the `Vec<BatchResult>` we just leaked above, still
+    // live until the caller drops `raw`.
+    let slots = unsafe { std::slice::from_raw_parts(raw.ptr, raw.len) };
+    let per_cmd = slots
+        .iter()
+        .map(|slot| {
+            if !slot.ok {
+                Err(())
+            } else if slot.out_len == 0 {
+                Ok(None)
+            } else {
+                let slice = unsafe { std::slice::from_raw_parts(slot.out_ptr, slot.out_len) };
+                let static_slice: &'static [u8] = unsafe { std::mem::transmute(slice) };
+                Ok(Some(static_slice))
+            }
+        })
+        .collect();
+
+    (per_cmd, raw)
+}
+
+// Outcome of running one pipelined batch of already-classified frames
+// through the FFI bridge: the replies to write back, in order, and whether
+// the batch ended on a malformed frame that should close the connection
+// once those replies are flushed.
+pub(crate) struct BatchOutcome {
+    pub(crate) replies: Vec<Reply>,
+    pub(crate) close_after: bool,
+}
+
+// Shared by the TCP/TLS, WebSocket, and io_uring transports: turns a batch
+// of classified frames into one `cpp_execute_batch` FFI crossing and the
+// replies for each item, in order. Transport-specific code only has to pull
+// `BatchOutcome::replies` out and write them through whatever framing it
+// uses, then close the connection if `close_after` is set.
+pub(crate) fn process_batch(mut items: Vec<PipelineItem>) -> BatchOutcome {
+    // Nothing after a protocol error should reach the backend: the client
+    // is told the connection is closing right after it, so a command that
+    // rode along in the same pipelined read (e.g. a SET behind a malformed
+    // frame) must never execute as a silent "ghost write".
+    if let Some(i) = items.iter().position(|item| matches!(item, PipelineItem::ProtocolError)) {
+        items.truncate(i + 1);
+    }
+
+    let commands: Vec<Command> = items
+        .iter()
+        .filter_map(|item| match item {
+            PipelineItem::Command(cmd) => Some(cmd.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let (results, raw) = ffi_getset_batch(&commands);
+    let mut results = results.into_iter();
+    let mut replies = Vec::with_capacity(items.len());
+    let mut close_after = false;
+
+    for item in items {
+        match item {
+            PipelineItem::Command(cmd) => {
+                let result = results.next().expect("one result per command sent to ffi_getset_batch");
+                replies.push(match cmd.op {
+                    OpCode::Get => match result {
+                        Err(_) => Reply::Error("backend"),
+                        Ok(None) => Reply::NilBulk,
+                        Ok(Some(bytes)) => Reply::Bulk(Bytes::copy_from_slice(bytes)),
+                    },
+                    OpCode::Set => match result {
+                        Err(_) => Reply::Error("backend"),
+                        Ok(_) => Reply::Ok,
+                    },
+                    _ => unreachable!(),
+                });
+            }
+            PipelineItem::Unsupported => replies.push(Reply::Error("unsupported command")),
+            PipelineItem::ProtocolError => {
+                replies.push(Reply::Error("protocol error"));
+                close_after = true;
+                break;
+            }
+        }
+    }
+
+    // One free for the whole result block (no per-key malloc/free churn),
+    // whether the batch ran to completion or stopped early on a protocol
+    // error.
+    unsafe { cpp_free_batch(raw.ptr, raw.len) };
+
+    BatchOutcome { replies, close_after }
+}
+
 // ===== Runtime + server =====
 
 #[no_mangle]
-pub extern "C" fn rust_init(n_threads: usize) -> bool {
+pub extern "C" fn rust_init(
+    n_threads: usize,
+    listeners_ptr: *const CListenerConfig,
+    n_listeners: usize,
+) -> bool {
+    // SAFETY: caller guarantees `listeners_ptr`/`n_listeners` describe a
+    // valid array of `CListenerConfig`s that outlives this call (see
+    // `config::parse_listener_configs`).
+    let mut bind_addrs = unsafe { config::parse_listener_configs(listeners_ptr, n_listeners) };
+    if bind_addrs.is_empty() {
+        // Preserve the original hard-coded behavior when the C++ host
+        // doesn't pass any listener config.
+        bind_addrs.push(BindAddr::Tcp("127.0.0.1:6380".to_string()));
+    }
+
+    // The `iouring` feature swaps the epoll-based Tokio reactor for
+    // compio's io_uring backend. It serves plain TCP listeners only (Unix
+    // domain socket and WebSocket listeners are skipped, and TLS is not
+    // applied), and `rust_shutdown` has no handle to stop it; this function
+    // returns before touching the Tokio runtime.
+    #[cfg(feature = "iouring")]
+    {
+        for bind_addr in &bind_addrs {
+            match bind_addr {
+                BindAddr::Tcp(addr) => iouring::spawn(addr.clone()),
+                other => eprintln!(
+                    "iouring backend doesn't support this listener, skipping it: {other:?}"
+                ),
+            }
+        }
+        true
+    }
+
+    #[cfg(not(feature = "iouring"))]
+    {
+        init_tokio(n_threads, bind_addrs)
+    }
+}
+
+#[cfg(not(feature = "iouring"))]
+fn init_tokio(n_threads: usize, bind_addrs: Vec<BindAddr>) -> bool {
     let max_blocking = 4;
 
     let rt = match tokio::runtime::Builder::new_multi_thread()
@@ -180,165 +405,280 @@ pub extern "C" fn rust_init(n_threads: usize) -> bool {
         Err(e) => { eprintln!("Failed to create tokio runtime: {e}"); return false; }
     };
 
-    std::thread::spawn(move || {
-        rt.block_on(async {
-            if let Err(e) = start_async_server().await {
-                eprintln!("Async server error: {e}");
-            }
-        });
+    let token = CancellationToken::new();
+    let tracker = TaskTracker::new();
+
+    std::thread::spawn({
+        let token = token.clone();
+        let tracker = tracker.clone();
+        move || {
+            rt.block_on(async {
+                let _ = SERVER_HANDLE.set(ServerHandle {
+                    token: token.clone(),
+                    tracker: tracker.clone(),
+                    runtime: tokio::runtime::Handle::current(),
+                });
+                if let Err(e) = start_async_server(bind_addrs, token, tracker).await {
+                    eprintln!("Async server error: {e}");
+                }
+            });
+        }
     });
 
     true
 }
 
-async fn start_async_server() -> std::io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:6380").await?;
-    println!("Async Rust server started on 127.0.0.1:6380");
+// Cancels the server's `CancellationToken` and blocks until every in-flight
+// client task has drained, so the C++ host can stop the embedded server and
+// flush the KV backend without killing the process mid-request.
+#[no_mangle]
+pub extern "C" fn rust_shutdown() {
+    let Some(handle) = SERVER_HANDLE.get() else {
+        return; // rust_init was never called (or never finished starting up)
+    };
+    handle.token.cancel();
+    handle.runtime.block_on(async {
+        handle.tracker.close();
+        handle.tracker.wait().await;
+    });
+}
 
+async fn start_async_server(
+    bind_addrs: Vec<BindAddr>,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> std::io::Result<()> {
     let use_db = true;
 
-    loop {
-        let (stream, _) = listener.accept().await?;
-        if let Err(e) = stream.set_nodelay(true) {
-            eprintln!("Failed to set TCP_NODELAY: {e}");
+    // Optional: terminate TLS for clients connecting via `rediss://`. If the
+    // cert/key pair isn't present we fall back to plaintext-only, so a dev
+    // checkout without certs still works exactly as before.
+    let tls_acceptor = match tls::build_acceptor(TLS_CERT_PATH, TLS_KEY_PATH) {
+        Ok(acceptor) => {
+            println!("TLS enabled (ALPN: {:?})", String::from_utf8_lossy(RESP3_ALPN_PROTOCOL));
+            Some(acceptor)
         }
-        tokio::spawn({
-            let use_db = use_db;
-            async move {
-                let res = if use_db {
-                    handle_client_async(stream).await      // original, with DB
-                } else {
-                    handle_client_async_nodb(stream).await // new, no DB
-                };
-                if let Err(e) = res {
-                    eprintln!("Client handling error: {e}");
-                }
+        Err(e) => {
+            eprintln!("TLS disabled, failed to load cert/key: {e}");
+            None
+        }
+    };
+
+    // One accept loop per configured listener (e.g. a TCP socket for remote
+    // clients, a Unix domain socket for local ones, a WebSocket listener for
+    // browser/relayed clients), all feeding the same `tracker` so shutdown
+    // still drains every connection.
+    let mut accept_tasks = Vec::with_capacity(bind_addrs.len());
+    for bind_addr in bind_addrs {
+        match bind_addr {
+            BindAddr::Ws(addr) => {
+                let listener = TcpListener::bind(&addr).await?;
+                println!("Async Rust server listening (WebSocket) on {addr}");
+                accept_tasks.push(tokio::spawn(ws::accept_loop(listener, token.clone(), tracker.clone())));
             }
-        });
+            bind_addr => {
+                let listener = listener::bind(&bind_addr).await?;
+                println!("Async Rust server listening on {bind_addr:?}");
+                accept_tasks.push(tokio::spawn(accept_loop(
+                    listener,
+                    tls_acceptor.clone(),
+                    use_db,
+                    token.clone(),
+                    tracker.clone(),
+                )));
+            }
+        }
     }
-}
 
-async fn handle_client_async(stream: TcpStream) -> std::io::Result<()> {
-    let mut resp3 = Resp3Handler::new(10 * 1024 * 1024);
-    
-    // Buffer size choice: 16KB read buffer
-    // Reference: Redis uses PROTO_IOBUF_LEN = 16384 (16KB) in networking.c
-    // See: https://github.com/redis/redis/blob/unstable/src/networking.c
-    // Rationale: Large enough to read many pipelined commands in one syscall,
-    // but small enough to avoid excessive memory overhead per client
-    let mut read_buf = [0u8; 16384];
-
-    // Split stream into reader and writer, wrap writer in buffer
-    let (mut reader, writer) = stream.into_split();
-    
-    // Buffer size choice: 16KB write buffer
-    // Reference: Redis uses PROTO_REPLY_CHUNK_BYTES = 16384 for reply buffers
-    // See: https://github.com/redis/redis/blob/unstable/src/networking.c
-    // Rationale: Batches multiple responses together to amortize syscall overhead.
-    // With redis-benchmark -P 1000, this allows ~100-200 responses per flush
-    // depending on response sizes (simple OK vs bulk strings)
-    let mut writer = BufWriter::with_capacity(16384, writer);
-
-    loop {
-        // Read once - in pipelined mode this may contain hundreds of commands
-        match reader.read(&mut read_buf).await {
-            Ok(0) => break,
-            Ok(n) => resp3.read_bytes(&read_buf[..n]),
-            Err(e) => return Err(e),
+    for task in accept_tasks {
+        if let Err(e) = task.await {
+            eprintln!("listener task panicked: {e}");
         }
+    }
 
-        // CRITICAL CHANGE: Process ALL available frames without intermediate flushes
-        loop {
-            match resp3.next_frame() {
-                Ok(Some(frame)) => {
-                    if let Some(cmd) = parse_resp3(frame) {
-                        match cmd.op {
-                            OpCode::Get => {
-                                match ffi_getset(&cmd) {
-                                    Err(_) => write_err(&mut writer, "backend").await?,
-                                    Ok(None) => write_nil_bulk(&mut writer).await?,
-                                    Ok(Some(bytes)) => {
-                                        write_bulk(&mut writer, bytes).await?;
-                                        unsafe { cpp_free_buf(bytes.as_ptr() as *mut u8, bytes.len()) };
-                                    }
-                                }
-                            }
-                            OpCode::Set => {
-                                match ffi_getset(&cmd) {
-                                    Err(_) => write_err(&mut writer, "backend").await?,
-                                    Ok(_)  => write_simple_ok(&mut writer).await?,
-                                }
-                            }
-                            _ => unreachable!(),
+    tracker.close();
+    Ok(())
+}
+
+async fn accept_loop(
+    listener: Box<dyn StreamListener>,
+    tls_acceptor: Option<tokio_rustls::TlsAcceptor>,
+    use_db: bool,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> std::io::Result<()> {
+    loop {
+        let stream = tokio::select! {
+            res = listener.accept() => res?,
+            _ = token.cancelled() => {
+                println!("shutdown requested, no longer accepting connections");
+                break;
+            }
+        };
+        let tls_acceptor = tls_acceptor.clone();
+        let conn_token = token.clone();
+        tracker.spawn(async move {
+            let res = match tls_acceptor {
+                Some(acceptor) => match acceptor.accept(stream).await {
+                    Ok(tls_stream) => {
+                        let alpn_protocol = tls_stream.get_ref().1.alpn_protocol().map(|p| p.to_vec());
+                        if use_db {
+                            handle_client_async(tls_stream, conn_token, alpn_protocol).await
+                        } else {
+                            handle_client_async_nodb(tls_stream, conn_token, alpn_protocol).await
                         }
+                    }
+                    Err(e) => {
+                        eprintln!("TLS handshake failed: {e}");
+                        return;
+                    }
+                },
+                None => {
+                    if use_db {
+                        handle_client_async(stream, conn_token, None).await      // original, with DB
                     } else {
-                        write_err(&mut writer, "unsupported command").await?;
+                        handle_client_async_nodb(stream, conn_token, None).await // new, no DB
                     }
                 }
-                Ok(None) => break,  // No more complete frames available
-                Err(_) => {
-                    write_err(&mut writer, "protocol error").await?;
-                    break;
-                }
+            };
+            if let Err(e) = res {
+                eprintln!("Client handling error: {e}");
             }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_client_async<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    token: CancellationToken,
+    // Negotiated during the TLS handshake (`None` for plaintext connections).
+    // Not branched on yet, but kept in scope here rather than just logged in
+    // `accept_loop` so a future multiplexer can dispatch on it per connection.
+    alpn_protocol: Option<Vec<u8>>,
+) -> std::io::Result<()> {
+    if let Some(proto) = &alpn_protocol {
+        println!("negotiated ALPN protocol: {:?}", String::from_utf8_lossy(proto));
+    }
+
+    // Split stream into reader and writer. `tokio::io::split` (rather than
+    // `TcpStream::into_split`) is what keeps this generic over both plain
+    // `TcpStream`s and TLS streams.
+    let (reader, writer) = io::split(stream);
+
+    // `FramedRead`/`FramedWrite` over `Resp3Codec` replace the old fixed
+    // 16KB `read_buf` + `Resp3Handler::next_frame()` loop: partial frames
+    // fall out as `Ok(None)` and the codec splits each frame off the shared
+    // buffer without a scratch copy. `parse_resp3` still copies each key and
+    // value into its own `Bytes`.
+    let mut framed_reader = FramedRead::new(reader, Resp3Codec);
+    let mut framed_writer = FramedWrite::new(writer, Resp3Codec);
+
+    'connection: loop {
+        // Block for the first frame of a new batch.
+        let first = tokio::select! {
+            frame = framed_reader.next() => match frame {
+                Some(result) => result,
+                None => break,
+            },
+            _ = token.cancelled() => break,
+        };
+
+        // Drain whatever else is already sitting in the read buffer from
+        // the same syscall (pipelined commands) without waiting on more
+        // I/O, so the whole batch can cross the FFI boundary in one call
+        // instead of once per command.
+        let mut frames = vec![first];
+        while let Some(Some(next)) = framed_reader.next().now_or_never() {
+            let is_err = next.is_err();
+            frames.push(next);
+            // A malformed-frame error leaves `buf` unadvanced, so calling `.next()`
+            // again would just return the same `Err` forever with no await
+            // point in between — a synchronous livelock. Stop draining and
+            // let `process_batch` close the connection after this frame.
+            if is_err {
+                break;
+            }
+        }
+
+        let items: Vec<PipelineItem> = frames.into_iter().map(classify_frame).collect();
+        let outcome = process_batch(items);
+
+        for reply in outcome.replies {
+            framed_writer.feed(reply).await?;
+        }
+        framed_writer.flush().await?;
+
+        if outcome.close_after {
+            break 'connection;
         }
-        
-        // CRITICAL CHANGE: Single flush after processing entire batch
-        // Original code flushed every 100 operations AND after each read batch.
-        // New behavior: Only flush after exhausting all parseable frames from current buffer.
-        // This matches Redis's event loop pattern and is essential for pipeline performance.
-        writer.flush().await?;
     }
-    
+
     Ok(())
 }
 
-async fn handle_client_async_nodb(stream: TcpStream) -> std::io::Result<()> {
-    let mut resp3 = Resp3Handler::new(10 * 1024 * 1024);
-    let mut read_buf = [0u8; 16384];
+// What a single decoded frame turned into, keeping pipeline ordering intact
+// across frames that parse to a `Command` and frames that don't.
+enum PipelineItem {
+    Command(Command),
+    Unsupported,
+    ProtocolError,
+}
 
-    let (mut reader, writer) = stream.into_split();
-    let mut writer = BufWriter::with_capacity(16384, writer);
+fn classify_frame(frame: Result<BytesFrame, codec::CodecError>) -> PipelineItem {
+    match frame {
+        Ok(frame) => match parse_resp3(frame) {
+            Some(cmd) => PipelineItem::Command(cmd),
+            None => PipelineItem::Unsupported,
+        },
+        Err(_) => PipelineItem::ProtocolError,
+    }
+}
+
+async fn handle_client_async_nodb<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    token: CancellationToken,
+    _alpn_protocol: Option<Vec<u8>>,
+) -> std::io::Result<()> {
+    let (reader, writer) = io::split(stream);
+    let mut framed_reader = FramedRead::new(reader, Resp3Codec);
+    let mut framed_writer = FramedWrite::new(writer, Resp3Codec);
 
     // Dummy value to return for GET hits (8 bytes, like your -d 8)
     const DUMMY_VALUE: &[u8] = b"AAAAAAAA";
 
     loop {
-        // Read a batch of pipelined commands
-        match reader.read(&mut read_buf).await {
-            Ok(0) => break,
-            Ok(n) => resp3.read_bytes(&read_buf[..n]),
-            Err(e) => return Err(e),
-        }
-
-        loop {
-            match resp3.next_frame() {
-                Ok(Some(frame)) => {
-                    if let Some(cmd) = parse_resp3(frame) {
-                        match cmd.op {
-                            OpCode::Get => {
-                                write_bulk(&mut writer, DUMMY_VALUE).await?;
-                            }
-                            OpCode::Set => {
-                                write_simple_ok(&mut writer).await?;
-                            }
-                            _ => {
-                                write_err(&mut writer, "unsupported command").await?;
-                            }
+        let result = tokio::select! {
+            frame = framed_reader.next() => match frame {
+                Some(result) => result,
+                None => break,
+            },
+            _ = token.cancelled() => break,
+        };
+        match result {
+            Ok(frame) => {
+                if let Some(cmd) = parse_resp3(frame) {
+                    match cmd.op {
+                        OpCode::Get => {
+                            framed_writer.send(Reply::Bulk(Bytes::from_static(DUMMY_VALUE))).await?;
+                        }
+                        OpCode::Set => {
+                            framed_writer.send(Reply::Ok).await?;
+                        }
+                        _ => {
+                            framed_writer.send(Reply::Error("unsupported command")).await?;
                         }
-                    } else {
-                        write_err(&mut writer, "unsupported command").await?;
                     }
+                } else {
+                    framed_writer.send(Reply::Error("unsupported command")).await?;
                 }
-                Ok(None) => break,
-                Err(_) => {
-                    write_err(&mut writer, "protocol error").await?;
-                    break;
-                }
+            }
+            Err(_) => {
+                framed_writer.send(Reply::Error("protocol error")).await?;
+                break;
             }
         }
-
-        writer.flush().await?;
     }
 
     Ok(())
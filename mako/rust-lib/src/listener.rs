@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, UnixListener};
+
+use crate::config::BindAddr;
+
+// Marker trait so `Box<dyn StreamListener>::accept` can hand back a single
+// boxed type regardless of transport; blanket-implemented for anything that
+// already satisfies the bounds `handle_client_async` needs.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for T {}
+
+// Transport-agnostic accept loop source. `handle_client_async` only ever
+// sees a `Box<dyn AsyncStream>` (or a TLS stream wrapping one), so the same
+// pipeline serves TCP and Unix domain socket clients without caring which
+// one it is.
+#[async_trait]
+pub trait StreamListener: Send {
+    async fn accept(&self) -> std::io::Result<Box<dyn AsyncStream>>;
+}
+
+pub struct TcpStreamListener(TcpListener);
+
+#[async_trait]
+impl StreamListener for TcpStreamListener {
+    async fn accept(&self) -> std::io::Result<Box<dyn AsyncStream>> {
+        let (stream, _) = self.0.accept().await?;
+        if let Err(e) = stream.set_nodelay(true) {
+            eprintln!("Failed to set TCP_NODELAY: {e}");
+        }
+        Ok(Box::new(stream))
+    }
+}
+
+pub struct UnixStreamListener(UnixListener);
+
+#[async_trait]
+impl StreamListener for UnixStreamListener {
+    async fn accept(&self) -> std::io::Result<Box<dyn AsyncStream>> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(Box::new(stream))
+    }
+}
+
+// Binds a `BindAddr` to the matching listener type. For Unix sockets, a
+// stale socket file left behind by a prior run (e.g. after a crash) is
+// removed first so the bind doesn't fail with `AddrInUse`.
+//
+// `BindAddr::Ws` never reaches this function: it has its own framing layer
+// (HTTP upgrade + WebSocket messages, not a raw `AsyncStream`), so
+// `start_async_server` binds it directly to `ws::accept_loop` instead.
+pub async fn bind(addr: &BindAddr) -> std::io::Result<Box<dyn StreamListener>> {
+    match addr {
+        BindAddr::Tcp(addr) => Ok(Box::new(TcpStreamListener(TcpListener::bind(addr).await?))),
+        BindAddr::Unix(path) => {
+            let _ = std::fs::remove_file(path);
+            Ok(Box::new(UnixStreamListener(UnixListener::bind(path)?)))
+        }
+        BindAddr::Ws(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "WebSocket listeners are bound via ws::accept_loop, not listener::bind",
+        )),
+    }
+}
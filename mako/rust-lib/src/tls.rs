@@ -0,0 +1,41 @@
+use std::io::BufReader;
+use std::sync::Arc;
+
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::TlsAcceptor;
+
+// Advertised during the TLS handshake so a future multiplexer (e.g. one that
+// also speaks HTTP/2 off the same port) can branch on the negotiated
+// protocol instead of guessing from the first bytes.
+pub const RESP3_ALPN_PROTOCOL: &[u8] = b"resp3";
+
+fn load_cert_chain(path: &str) -> std::io::Result<Vec<CertificateDer<'static>>> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = BufReader::new(file);
+    rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()
+}
+
+fn load_private_key(path: &str) -> std::io::Result<PrivateKeyDer<'static>> {
+    let file = std::fs::File::open(path)?;
+    let mut reader = BufReader::new(file);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "no private key found in key file"))
+}
+
+// Build a `TlsAcceptor` from a PEM cert chain + private key on disk, with
+// `resp3` offered as the sole ALPN protocol. Called once at startup; the
+// resulting acceptor is cheap to clone (it's an `Arc` under the hood) and is
+// shared across every accepted connection.
+pub fn build_acceptor(cert_path: &str, key_path: &str) -> std::io::Result<TlsAcceptor> {
+    let certs = load_cert_chain(cert_path)?;
+    let key = load_private_key(key_path)?;
+
+    let mut config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    config.alpn_protocols = vec![RESP3_ALPN_PROTOCOL.to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
@@ -0,0 +1,112 @@
+// RESP3-over-WebSocket transport: upgrades incoming HTTP connections, then
+// treats each binary WebSocket message's payload as bytes fed into the same
+// RESP3 parse path (`parse_resp3`) and FFI bridge as the plain TCP/TLS
+// listeners, only swapping the framing layer. Lets browser clients or
+// relayed connections reach the backend through environments where only
+// HTTP/WS egress is allowed.
+use async_tungstenite::tokio::accept_async;
+use async_tungstenite::tungstenite::Message;
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_util::codec::{Decoder, Encoder};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+use crate::codec::Resp3Codec;
+use crate::{classify_frame, process_batch, PipelineItem};
+
+fn ws_err(e: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+}
+
+pub async fn accept_loop(
+    listener: TcpListener,
+    token: CancellationToken,
+    tracker: TaskTracker,
+) -> std::io::Result<()> {
+    loop {
+        let stream = tokio::select! {
+            res = listener.accept() => res?.0,
+            _ = token.cancelled() => {
+                println!("shutdown requested, no longer accepting WebSocket connections");
+                break;
+            }
+        };
+        let conn_token = token.clone();
+        tracker.spawn(async move {
+            if let Err(e) = handle_connection(stream, conn_token).await {
+                eprintln!("WebSocket client handling error: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn handle_connection(stream: tokio::net::TcpStream, token: CancellationToken) -> std::io::Result<()> {
+    let ws_stream = accept_async(stream).await.map_err(ws_err)?;
+    let (mut write, mut read) = ws_stream.split();
+    let mut codec = Resp3Codec;
+
+    // Lives across messages (unlike the old per-message `BytesMut`) so a
+    // RESP3 command split across two WS binary messages doesn't get its
+    // tail silently dropped, mirroring the persistent buffer `FramedRead`
+    // keeps for the TCP/TLS path.
+    let mut buf = BytesMut::new();
+
+    'connection: loop {
+        let msg = tokio::select! {
+            msg = read.next() => match msg {
+                Some(Ok(msg)) => msg,
+                Some(Err(e)) => return Err(ws_err(e)),
+                None => break,
+            },
+            _ = token.cancelled() => break,
+        };
+
+        // RESP3 only ever travels inside binary frames; anything else
+        // (text, ping/pong, a close handshake) is handled by tungstenite or
+        // simply ignored.
+        let payload = match msg {
+            Message::Binary(payload) => payload,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        buf.extend_from_slice(&payload);
+        let mut frames = Vec::new();
+        loop {
+            match codec.decode(&mut buf) {
+                Ok(Some(frame)) => frames.push(Ok(frame)),
+                Ok(None) => break,
+                Err(e) => {
+                    frames.push(Err(e));
+                    break;
+                }
+            }
+        }
+
+        // A RESP3 command split across two WS binary messages yields zero
+        // frames on the message that only carries its first half; skip the
+        // batch entirely rather than sending the client a spurious empty
+        // binary reply in between real ones.
+        if frames.is_empty() {
+            continue;
+        }
+
+        let items: Vec<PipelineItem> = frames.into_iter().map(classify_frame).collect();
+        let outcome = process_batch(items);
+
+        let mut out = BytesMut::new();
+        for reply in outcome.replies {
+            codec.encode(reply, &mut out).map_err(ws_err)?;
+        }
+        write.send(Message::Binary(out.freeze().to_vec())).await.map_err(ws_err)?;
+
+        if outcome.close_after {
+            break 'connection;
+        }
+    }
+
+    Ok(())
+}